@@ -1,19 +1,333 @@
-use heck::SnakeCase;
+use heck::{CamelCase, KebabCase, MixedCase, ShoutySnakeCase, SnakeCase};
 use indoc::formatdoc;
 use regex::Regex;
 use std::borrow::Cow;
+use std::collections::HashMap;
 use std::error::Error;
+use std::io::{BufRead, Read};
+
+// The casing strategy to use for the JSON keys in the generated codecs.
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default)]
+enum Casing {
+    #[default]
+    Snake,
+    Kebab,
+    Camel,
+    Pascal,
+    ScreamingSnake,
+}
+
+impl Casing {
+    fn from_flag(flag: &str) -> Result<Casing, Box<dyn Error>> {
+        match flag {
+            "snake_case" => Ok(Casing::Snake),
+            "kebab-case" => Ok(Casing::Kebab),
+            "camelCase" => Ok(Casing::Camel),
+            "PascalCase" => Ok(Casing::Pascal),
+            "SCREAMING_SNAKE_CASE" => Ok(Casing::ScreamingSnake),
+            other => Err(format!("Unknown casing strategy: {}", other).into()),
+        }
+    }
+
+    fn apply(&self, s: &str) -> String {
+        match self {
+            Casing::Snake => s.to_snake_case(),
+            Casing::Kebab => s.to_kebab_case(),
+            Casing::Camel => s.to_mixed_case(),
+            Casing::Pascal => s.to_camel_case(),
+            Casing::ScreamingSnake => s.to_shouty_snake_case(),
+        }
+    }
+}
 
 fn main() -> Result<(), Box<dyn Error>> {
-    let mut line = String::new();
-    std::io::stdin().read_line(&mut line)?;
-    line = line.trim_end().to_owned();
-    let case_class = parse(&line)?;
-    println!("{}", case_class.companion_object());
+    let args: Vec<String> = std::env::args().collect();
+    let casing = match args.iter().position(|a| a == "--case") {
+        Some(i) => {
+            let flag = args
+                .get(i + 1)
+                .ok_or("--case requires a value")?;
+            Casing::from_flag(flag)?
+        }
+        None => Casing::default(),
+    };
+
+    if args.iter().any(|a| a == "--repl") {
+        run_repl(casing)
+    } else {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        println!("{}", render_batch(&input, casing)?);
+        Ok(())
+    }
+}
+
+// Renders a single case class or sealed trait definition into its companion object(s).
+fn render_definition(input: &str, casing: Casing) -> Result<String, Box<dyn Error>> {
+    if input.contains("sealed trait") {
+        Ok(parse_sealed_trait(input)?.companion_object(casing))
+    } else {
+        Ok(parse(input)?.companion_object(casing))
+    }
+}
+
+// Segments all of `input` into individual definitions and renders each one, separated by blank
+// lines. `input` may hold any mix of standalone case class definitions and sealed trait
+// hierarchies.
+fn render_batch(input: &str, casing: Casing) -> Result<String, Box<dyn Error>> {
+    let outputs: Result<Vec<String>, Box<dyn Error>> = segment_definitions(input)
+        .iter()
+        .map(|segment| render_definition(segment, casing))
+        .collect();
+
+    Ok(outputs?.join("\n\n"))
+}
+
+// Splits `input` into the text of each top-level definition, so that a file containing many
+// case classes and/or sealed trait hierarchies (possibly pasted across several lines each) can
+// be rendered one definition at a time.
+fn segment_definitions(input: &str) -> Vec<String> {
+    let start_regex = Regex::new(r"sealed\ trait\ \w+|case\ class\ \w+").unwrap();
+    let mut segments = vec![];
+    let mut search_from = 0;
+
+    while let Some(found) = start_regex.find(&input[search_from..]) {
+        let start = search_from + found.start();
+        let rest = &input[start..];
+
+        let end = if found.as_str().starts_with("sealed trait") {
+            sealed_trait_segment_end(rest)
+        } else {
+            case_class_segment_end(rest)
+        };
+
+        segments.push(rest[..end].trim_end().to_owned());
+        search_from = start + end;
+    }
+
+    segments
+}
+
+// Finds the end of the case class definition starting at the beginning of `rest`, i.e. the
+// closing paren that matches its first, opening one.
+fn case_class_segment_end(rest: &str) -> usize {
+    let mut depth = 0i32;
+    let mut seen_paren = false;
+    let mut end = rest.len();
+
+    for (i, c) in rest.char_indices() {
+        match c {
+            '(' => {
+                seen_paren = true;
+                depth += 1;
+            }
+            '[' if seen_paren => depth += 1,
+            ']' if seen_paren => depth -= 1,
+            ')' => {
+                depth -= 1;
+                if seen_paren && depth == 0 {
+                    end = i + c.len_utf8();
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    end
+}
+
+// Finds the end of the sealed trait hierarchy starting at the beginning of `rest`, i.e. its
+// header followed by all of its subtype lines. Subtype lines can't otherwise be told apart from
+// "more to come", so (mirroring the REPL) the hierarchy is considered done at the first blank
+// line, or at the end of input if there isn't one.
+fn sealed_trait_segment_end(rest: &str) -> usize {
+    let mut depth = 0i32;
+    let mut line_start = 0;
+
+    for line in rest.split_inclusive('\n') {
+        let trimmed = line.trim_end_matches('\n');
+        if depth == 0 && line_start > 0 && trimmed.trim().is_empty() {
+            return line_start;
+        }
+
+        for c in trimmed.chars() {
+            match c {
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth -= 1,
+                _ => {}
+            }
+        }
+
+        line_start += line.len();
+    }
+
+    rest.len()
+}
+
+// A REPL that accumulates lines of input until the buffer looks like a complete definition, then
+// attempts to parse and render it as a single definition. On a parse error the buffer is kept so
+// the user can correct it rather than retyping from scratch. A `case class` is considered
+// complete as soon as its parens/brackets balance, but a `sealed trait` may have any number of
+// subtype lines following its header, none of which are individually distinguishable from the
+// last, so it's only considered complete once the user enters a blank line.
+fn run_repl(casing: Casing) -> Result<(), Box<dyn Error>> {
+    let stdin = std::io::stdin();
+    let mut buffer = String::new();
+
+    for line in stdin.lock().lines() {
+        let line = line?;
+        let line_is_blank = line.trim().is_empty();
+        if !buffer.is_empty() {
+            buffer.push('\n');
+        }
+        buffer.push_str(&line);
+
+        if !is_complete(&buffer, line_is_blank) {
+            continue;
+        }
+
+        match render_definition(&buffer, casing) {
+            Ok(output) => {
+                println!("{}\n", output);
+                buffer.clear();
+            }
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    }
+
     Ok(())
 }
 
+// Whether the REPL's accumulated `buffer` is ready to be parsed and rendered. `line_is_blank`
+// is whether the line just appended to `buffer` was blank, which is how the user signals that a
+// `sealed trait` (whose subtypes can't otherwise be distinguished from "more to come") is done.
+fn is_complete(buffer: &str, line_is_blank: bool) -> bool {
+    if buffer.contains("sealed trait") {
+        // A hierarchy of only `case object`s has no brackets at all, and is balanced as it is.
+        let has_bracket = buffer.contains(|c| matches!(c, '(' | ')' | '[' | ']'));
+        line_is_blank && (!has_bracket || is_balanced(buffer))
+    } else {
+        is_balanced(buffer)
+    }
+}
+
+// Whether `s` has a matching number of `(`/`)` and `[`/`]`, and contains at least one of them.
+// Used by the REPL to decide when an accumulated buffer is syntactically complete enough to
+// attempt a parse.
+fn is_balanced(s: &str) -> bool {
+    let mut depth = 0i32;
+    let mut saw_bracket = false;
+
+    for c in s.chars() {
+        match c {
+            '(' | '[' => {
+                saw_bracket = true;
+                depth += 1;
+            }
+            ')' | ']' => depth -= 1,
+            _ => {}
+        }
+    }
+
+    saw_bracket && depth == 0
+}
+
+// Splits on top-level commas only, i.e. not ones nested inside `[...]` or `(...)`, so fields/types
+// like `Map[String, Int]` aren't mangled by a bare `split(",")`.
+fn split_top_level(s: &str) -> Vec<String> {
+    let mut segments = vec![];
+    let mut current = String::new();
+    let mut depth = 0i32;
+
+    for c in s.chars() {
+        match c {
+            '[' | '(' => {
+                depth += 1;
+                current.push(c);
+            }
+            ']' | ')' => {
+                depth -= 1;
+                current.push(c);
+            }
+            ',' if depth == 0 => {
+                segments.push(current.clone());
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+    segments.push(current);
+
+    segments
+}
+
+// Splits on top-level `;` or newlines, so a sealed trait header and its subtypes can be written
+// either `;`-joined on one line or one per line.
+fn split_statements(s: &str) -> Vec<String> {
+    let mut statements = vec![];
+    let mut current = String::new();
+    let mut depth = 0i32;
+
+    for c in s.chars() {
+        match c {
+            '[' | '(' => {
+                depth += 1;
+                current.push(c);
+            }
+            ']' | ')' => {
+                depth -= 1;
+                current.push(c);
+            }
+            ';' | '\n' if depth == 0 => {
+                statements.push(current.trim().to_owned());
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+    statements.push(current.trim().to_owned());
+
+    statements.into_iter().filter(|s| !s.is_empty()).collect()
+}
+
 fn parse(input: &str) -> Result<CaseClass, Box<dyn Error>> {
+    // Field name: the identifier immediately preceding the colon, ignoring any annotation that
+    // may precede it
+    let field_regex = Regex::new(r"(?P<field>\w+)\s*:")?;
+
+    // A per-field JSON key override, either a trailing `// @key("...")` comment or a leading
+    // `@JsonKey("...")` marker. These are associated with the nearest field name on the same
+    // line of `input`, before comma-splitting below (which would otherwise land a trailing
+    // `// @key("...")` comment in the following field's segment instead of its own).
+    let key_override_regex = Regex::new(r#"@(?P<kind>key|JsonKey)\(\s*"(?P<key>[^"]*)"\s*\)"#)?;
+    let mut key_overrides: HashMap<String, String> = HashMap::new();
+    for line in input.lines() {
+        let fields_in_line: Vec<_> = field_regex.captures_iter(line).collect();
+        for annotation in key_override_regex.captures_iter(line) {
+            let key = match annotation.name("key") {
+                Some(key) => key.as_str().to_owned(),
+                None => continue,
+            };
+            let annotation_pos = annotation.get(0).unwrap().start();
+            let field_names = fields_in_line.iter().filter_map(|c| c.name("field"));
+            // A leading `@JsonKey("...")` marker belongs to the next field name after it; a
+            // trailing `// @key("...")` comment belongs to the nearest one before it.
+            let field = if annotation.name("kind").map(|k| k.as_str()) == Some("JsonKey") {
+                field_names
+                    .filter(|m| m.start() >= annotation_pos)
+                    .min_by_key(|m| m.start())
+            } else {
+                field_names
+                    .filter(|m| m.start() < annotation_pos)
+                    .max_by_key(|m| m.start())
+            };
+            if let Some(field) = field {
+                key_overrides.insert(field.as_str().to_owned(), key);
+            }
+        }
+    }
+
     let input = input.replace("\n", "");
     let main_regex = Regex::new(
         r"(?x)
@@ -26,12 +340,9 @@ fn parse(input: &str) -> Result<CaseClass, Box<dyn Error>> {
         ",
     )?;
 
-    // Field name: just take everything before the colon
-    let field_regex = Regex::new(r"^\s*(?P<field>\w+):")?;
-
     // Type param regex: everything up to the first non-word character, with optional variance
     // character
-    let type_regex = Regex::new(r"^[+-]?(?P<type>\w+)")?;
+    let type_regex = Regex::new(r"^\s*[+-]?(?P<type>\w+)")?;
 
     let captures = main_regex
         .captures(&input)
@@ -45,8 +356,8 @@ fn parse(input: &str) -> Result<CaseClass, Box<dyn Error>> {
     let type_params: Result<Vec<String>, Box<dyn Error>> = captures
         .name("types")
         .map(|t| {
-            t.as_str()
-                .split(",")
+            split_top_level(t.as_str())
+                .iter()
                 .map(|t| {
                     let type_name = type_regex
                         .captures(t)
@@ -61,31 +372,87 @@ fn parse(input: &str) -> Result<CaseClass, Box<dyn Error>> {
 
     let fields = captures.name("fields").ok_or("Could not extract fields")?;
 
-    let field_names: Result<Vec<String>, Box<dyn Error>> = fields
-        .as_str()
-        .split(",")
+    let fields: Result<Vec<Field>, Box<dyn Error>> = split_top_level(fields.as_str())
+        .iter()
         .map(|f| {
             let field_name = field_regex
                 .captures(f)
                 .ok_or("Could not get capture groups for field name")?
                 .name("field")
                 .ok_or("Could not extract field name")?;
-            Ok(field_name.as_str().to_owned())
+            let key_override = key_overrides.get(field_name.as_str()).cloned();
+            Ok(Field {
+                name: field_name.as_str().to_owned(),
+                key_override,
+            })
         })
         .collect();
 
     Ok(CaseClass {
         name: class_name,
         type_params: type_params?,
-        fields: field_names?,
+        fields: fields?,
     })
 }
 
+// Parses a sealed trait hierarchy, e.g. `sealed trait Shape; case class Circle(radius: Double)
+// extends Shape; case object Empty extends Shape`. Statements may be separated by `;` or by
+// newlines, the first being the sealed trait header and the rest being the case class/case
+// object subtypes.
+fn parse_sealed_trait(input: &str) -> Result<SealedTrait, Box<dyn Error>> {
+    let trait_regex = Regex::new(r"sealed\ trait\ (?P<name>\w+)")?;
+    let object_regex = Regex::new(r"^\s*case\ object\ (?P<name>\w+)")?;
+
+    let statements = split_statements(input);
+    let mut statements = statements.iter().map(String::as_str);
+
+    let header = statements
+        .next()
+        .ok_or("Could not find sealed trait header")?;
+    let name = trait_regex
+        .captures(header)
+        .ok_or("Could not get capture groups for sealed trait")?
+        .name("name")
+        .ok_or("Could not extract sealed trait name")?
+        .as_str()
+        .to_owned();
+
+    let subtypes: Result<Vec<CaseClass>, Box<dyn Error>> = statements
+        .map(|statement| {
+            if let Some(captures) = object_regex.captures(statement) {
+                let object_name = captures
+                    .name("name")
+                    .ok_or("Could not extract case object name")?;
+                Ok(CaseClass {
+                    name: object_name.as_str().to_owned(),
+                    type_params: vec![],
+                    fields: vec![],
+                })
+            } else {
+                parse(statement)
+            }
+        })
+        .collect();
+
+    Ok(SealedTrait {
+        name,
+        subtypes: subtypes?,
+    })
+}
+
+// A single case class field, along with an optional override for the JSON key it should be
+// encoded/decoded under (in place of the configured Casing).
+#[derive(Debug, Eq, PartialEq, Clone)]
+struct Field {
+    name: String,
+    key_override: Option<String>,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 struct CaseClass {
     name: String,
     type_params: Vec<String>, // [A] and such
-    fields: Vec<String>,
+    fields: Vec<Field>,
 }
 
 impl CaseClass {
@@ -93,12 +460,16 @@ impl CaseClass {
         !self.type_params.is_empty()
     }
 
-    fn companion_object(&self) -> String {
-        // All the field names in snake case, joined into one comma-separated string
+    fn companion_object(&self, casing: Casing) -> String {
+        // The JSON key for each field: its override if one was given, otherwise its name
+        // transformed to the configured casing. Joined into one comma-separated string.
         let transformed_field_names = self
             .fields
             .iter()
-            .map(|s| format!("\"{}\"", s.to_snake_case()))
+            .map(|f| match &f.key_override {
+                Some(key) => format!("\"{}\"", key),
+                None => format!("\"{}\"", casing.apply(&f.name)),
+            })
             .collect::<Vec<String>>()
             .join(", ");
 
@@ -108,7 +479,7 @@ impl CaseClass {
             "a => ({})",
             self.fields
                 .iter()
-                .map(|s| format!("a.{}", s))
+                .map(|f| format!("a.{}", f.name))
                 .collect::<Vec<String>>()
                 .join(", ")
         );
@@ -170,11 +541,78 @@ impl CaseClass {
     }
 }
 
+// A sealed trait hierarchy, i.e. a trait together with the case class/case object subtypes
+// that extend it.
+#[derive(Debug, Eq, PartialEq)]
+struct SealedTrait {
+    name: String,
+    subtypes: Vec<CaseClass>,
+}
+
+impl SealedTrait {
+    /// Emits a companion object for each subtype (via `CaseClass::companion_object`), followed by
+    /// a companion object for the trait itself that dispatches on a `"type"` discriminator field.
+    fn companion_object(&self, casing: Casing) -> String {
+        let subtype_objects = self
+            .subtypes
+            .iter()
+            .map(|s| s.companion_object(casing))
+            .collect::<Vec<String>>()
+            .join("\n\n");
+
+        let encoder_cases = self
+            .subtypes
+            .iter()
+            .map(|s| {
+                format!(
+                    "    case v: {name} => {name}.encoder(v).mapObject(_.add(\"type\", Json.fromString(\"{name}\")))",
+                    name = s.name
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let decoder_cases = self
+            .subtypes
+            .iter()
+            .map(|s| format!("      case \"{name}\" => {name}.decoder(c)", name = s.name))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let dispatcher = formatdoc!(
+            "object {name} {{
+              implicit val encoder: Encoder[{name}] = Encoder.instance {{
+            {encoder_cases}
+              }}
+
+              implicit val decoder: Decoder[{name}] = Decoder.instance {{ c =>
+                c.downField(\"type\").as[String].flatMap {{
+            {decoder_cases}
+                  case other => Left(DecodingFailure(s\"Unknown type: $other\", c.history))
+                }}
+              }}
+            }}",
+            name = self.name,
+            encoder_cases = encoder_cases,
+            decoder_cases = decoder_cases
+        );
+
+        format!("{}\n\n{}", subtype_objects, dispatcher)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use indoc::indoc;
 
+    fn field(name: &str) -> Field {
+        Field {
+            name: name.to_owned(),
+            key_override: None,
+        }
+    }
+
     #[test]
     fn test_parse() {
         fn go(
@@ -191,7 +629,7 @@ mod tests {
                         .into_iter()
                         .map(|s| s.to_string())
                         .collect(),
-                    fields: expected_fields.into_iter().map(|s| s.to_string()).collect()
+                    fields: expected_fields.into_iter().map(|s| field(s)).collect()
                 }
             )
         }
@@ -241,6 +679,36 @@ mod tests {
             &["A"],
             &["something"],
         );
+        go(
+            "case class Prices(prices: Map[String, Int])",
+            "Prices",
+            &[],
+            &["prices"],
+        );
+        go(
+            "case class Pairs(pairs: List[(A, B)])",
+            "Pairs",
+            &[],
+            &["pairs"],
+        );
+        go(
+            "case class Nested(nested: List[Map[K, V]])",
+            "Nested",
+            &[],
+            &["nested"],
+        );
+        go(
+            "case class Multi(a: Map[String, Int], b: List[Map[K, V]])",
+            "Multi",
+            &[],
+            &["a", "b"],
+        );
+        go(
+            "case class Bounded[A <: Ordering[A], B](items: Map[A, B])",
+            "Bounded",
+            &["A", "B"],
+            &["items"],
+        );
     }
 
     #[test]
@@ -248,10 +716,10 @@ mod tests {
         let class = CaseClass {
             name: "Person".to_string(),
             type_params: vec![],
-            fields: vec!["age".to_string(), "favoriteFood".to_string()],
+            fields: vec![field("age"), field("favoriteFood")],
         };
         assert_eq!(
-            class.companion_object(),
+            class.companion_object(Casing::Snake),
             indoc!(
                 r#"
                 object Person {
@@ -268,10 +736,10 @@ mod tests {
         let class = CaseClass {
             name: "Generic".to_string(),
             type_params: vec!["A".to_string()],
-            fields: vec!["something".to_string()],
+            fields: vec![field("something")],
         };
         assert_eq!(
-            class.companion_object(),
+            class.companion_object(Casing::Snake),
             indoc!(
                 r#"
                 object Generic {
@@ -282,4 +750,395 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_companion_object_casing() {
+        let class = CaseClass {
+            name: "Person".to_string(),
+            type_params: vec![],
+            fields: vec![field("userId")],
+        };
+
+        assert_eq!(
+            class.companion_object(Casing::Kebab),
+            indoc!(
+                r#"
+                object Person {
+                  implicit lazy val encoder: Encoder[Person] = Encoder.forProduct1("user-id")(a => (a.userId))
+
+                  implicit lazy val decoder: Decoder[Person] = Decoder.forProduct1("user-id")(Person.apply)
+                }"#
+            )
+        );
+        assert_eq!(
+            class.companion_object(Casing::Camel),
+            indoc!(
+                r#"
+                object Person {
+                  implicit lazy val encoder: Encoder[Person] = Encoder.forProduct1("userId")(a => (a.userId))
+
+                  implicit lazy val decoder: Decoder[Person] = Decoder.forProduct1("userId")(Person.apply)
+                }"#
+            )
+        );
+        assert_eq!(
+            class.companion_object(Casing::Pascal),
+            indoc!(
+                r#"
+                object Person {
+                  implicit lazy val encoder: Encoder[Person] = Encoder.forProduct1("UserId")(a => (a.userId))
+
+                  implicit lazy val decoder: Decoder[Person] = Decoder.forProduct1("UserId")(Person.apply)
+                }"#
+            )
+        );
+        assert_eq!(
+            class.companion_object(Casing::ScreamingSnake),
+            indoc!(
+                r#"
+                object Person {
+                  implicit lazy val encoder: Encoder[Person] = Encoder.forProduct1("USER_ID")(a => (a.userId))
+
+                  implicit lazy val decoder: Decoder[Person] = Decoder.forProduct1("USER_ID")(Person.apply)
+                }"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_sealed_trait() {
+        let sealed_trait = parse_sealed_trait(
+            "sealed trait Shape; case class Circle(radius: Double) extends Shape; case object Empty extends Shape",
+        )
+        .unwrap();
+        assert_eq!(
+            sealed_trait,
+            SealedTrait {
+                name: "Shape".to_string(),
+                subtypes: vec![
+                    CaseClass {
+                        name: "Circle".to_string(),
+                        type_params: vec![],
+                        fields: vec![field("radius")],
+                    },
+                    CaseClass {
+                        name: "Empty".to_string(),
+                        type_params: vec![],
+                        fields: vec![],
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_sealed_trait_newline_separated() {
+        let sealed_trait = parse_sealed_trait(indoc!(
+            "
+            sealed trait Shape
+            case class Circle(radius: Double) extends Shape
+            case class Square(side: Double) extends Shape
+            case object Empty extends Shape
+            "
+        ))
+        .unwrap();
+        assert_eq!(
+            sealed_trait,
+            SealedTrait {
+                name: "Shape".to_string(),
+                subtypes: vec![
+                    CaseClass {
+                        name: "Circle".to_string(),
+                        type_params: vec![],
+                        fields: vec![field("radius")],
+                    },
+                    CaseClass {
+                        name: "Square".to_string(),
+                        type_params: vec![],
+                        fields: vec![field("side")],
+                    },
+                    CaseClass {
+                        name: "Empty".to_string(),
+                        type_params: vec![],
+                        fields: vec![],
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_sealed_trait_companion_object() {
+        let sealed_trait = SealedTrait {
+            name: "Shape".to_string(),
+            subtypes: vec![CaseClass {
+                name: "Circle".to_string(),
+                type_params: vec![],
+                fields: vec![field("radius")],
+            }],
+        };
+        assert_eq!(
+            sealed_trait.companion_object(Casing::Snake),
+            indoc!(
+                r#"
+                object Circle {
+                  implicit lazy val encoder: Encoder[Circle] = Encoder.forProduct1("radius")(a => (a.radius))
+
+                  implicit lazy val decoder: Decoder[Circle] = Decoder.forProduct1("radius")(Circle.apply)
+                }
+
+                object Shape {
+                  implicit val encoder: Encoder[Shape] = Encoder.instance {
+                    case v: Circle => Circle.encoder(v).mapObject(_.add("type", Json.fromString("Circle")))
+                  }
+
+                  implicit val decoder: Decoder[Shape] = Decoder.instance { c =>
+                    c.downField("type").as[String].flatMap {
+                      case "Circle" => Circle.decoder(c)
+                      case other => Left(DecodingFailure(s"Unknown type: $other", c.history))
+                    }
+                  }
+                }"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_segment_definitions() {
+        let input = indoc!(
+            "case class Person(age: Int)
+            case class Dog(
+                name: String,
+                breed: String
+            )"
+        );
+        assert_eq!(
+            segment_definitions(input),
+            vec![
+                "case class Person(age: Int)".to_string(),
+                "case class Dog(\n    name: String,\n    breed: String\n)".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_batch() {
+        let input = "case class Person(age: Int)\ncase class Dog(name: String)";
+        assert_eq!(
+            render_batch(input, Casing::Snake).unwrap(),
+            indoc!(
+                r#"
+                object Person {
+                  implicit lazy val encoder: Encoder[Person] = Encoder.forProduct1("age")(a => (a.age))
+
+                  implicit lazy val decoder: Decoder[Person] = Decoder.forProduct1("age")(Person.apply)
+                }
+
+                object Dog {
+                  implicit lazy val encoder: Encoder[Dog] = Encoder.forProduct1("name")(a => (a.name))
+
+                  implicit lazy val decoder: Decoder[Dog] = Decoder.forProduct1("name")(Dog.apply)
+                }"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_batch_sealed_trait_newline_separated() {
+        let input = indoc!(
+            "
+            sealed trait Shape
+            case class Circle(radius: Double) extends Shape
+            case object Empty extends Shape
+            "
+        );
+        assert_eq!(
+            render_batch(input, Casing::Snake).unwrap(),
+            indoc!(
+                r#"
+                object Circle {
+                  implicit lazy val encoder: Encoder[Circle] = Encoder.forProduct1("radius")(a => (a.radius))
+
+                  implicit lazy val decoder: Decoder[Circle] = Decoder.forProduct1("radius")(Circle.apply)
+                }
+
+                object Empty {
+                  implicit lazy val encoder: Encoder[Empty] = Encoder.forProduct0()(a => ())
+
+                  implicit lazy val decoder: Decoder[Empty] = Decoder.forProduct0()(Empty.apply)
+                }
+
+                object Shape {
+                  implicit val encoder: Encoder[Shape] = Encoder.instance {
+                    case v: Circle => Circle.encoder(v).mapObject(_.add("type", Json.fromString("Circle")))
+                    case v: Empty => Empty.encoder(v).mapObject(_.add("type", Json.fromString("Empty")))
+                  }
+
+                  implicit val decoder: Decoder[Shape] = Decoder.instance { c =>
+                    c.downField("type").as[String].flatMap {
+                      case "Circle" => Circle.decoder(c)
+                      case "Empty" => Empty.decoder(c)
+                      case other => Left(DecodingFailure(s"Unknown type: $other", c.history))
+                    }
+                  }
+                }"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_batch_mixed_case_class_and_sealed_trait() {
+        let input = indoc!(
+            "
+            case class Standalone(id: Int)
+
+            sealed trait Shape
+            case class Circle(radius: Double) extends Shape
+            case object Empty extends Shape
+            "
+        );
+        assert_eq!(
+            render_batch(input, Casing::Snake).unwrap(),
+            indoc!(
+                r#"
+                object Standalone {
+                  implicit lazy val encoder: Encoder[Standalone] = Encoder.forProduct1("id")(a => (a.id))
+
+                  implicit lazy val decoder: Decoder[Standalone] = Decoder.forProduct1("id")(Standalone.apply)
+                }
+
+                object Circle {
+                  implicit lazy val encoder: Encoder[Circle] = Encoder.forProduct1("radius")(a => (a.radius))
+
+                  implicit lazy val decoder: Decoder[Circle] = Decoder.forProduct1("radius")(Circle.apply)
+                }
+
+                object Empty {
+                  implicit lazy val encoder: Encoder[Empty] = Encoder.forProduct0()(a => ())
+
+                  implicit lazy val decoder: Decoder[Empty] = Decoder.forProduct0()(Empty.apply)
+                }
+
+                object Shape {
+                  implicit val encoder: Encoder[Shape] = Encoder.instance {
+                    case v: Circle => Circle.encoder(v).mapObject(_.add("type", Json.fromString("Circle")))
+                    case v: Empty => Empty.encoder(v).mapObject(_.add("type", Json.fromString("Empty")))
+                  }
+
+                  implicit val decoder: Decoder[Shape] = Decoder.instance { c =>
+                    c.downField("type").as[String].flatMap {
+                      case "Circle" => Circle.decoder(c)
+                      case "Empty" => Empty.decoder(c)
+                      case other => Left(DecodingFailure(s"Unknown type: $other", c.history))
+                    }
+                  }
+                }"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_is_balanced() {
+        assert!(!is_balanced("case class Person("));
+        assert!(!is_balanced("case class Person(age: Int"));
+        assert!(is_balanced("case class Person(age: Int)"));
+        assert!(is_balanced("case class Generic[A](something: List[A])"));
+    }
+
+    #[test]
+    fn test_is_complete() {
+        // A case class is complete as soon as its parens balance, blank line or not.
+        assert!(is_complete("case class Person(age: Int)", false));
+        assert!(!is_complete("case class Person(age: Int", false));
+
+        // A sealed trait isn't complete after just its first subtype's parens balance...
+        assert!(!is_complete(
+            "sealed trait Shape\ncase class Circle(radius: Double) extends Shape",
+            false
+        ));
+        // ...only once a blank line signals there are no more subtypes to come.
+        assert!(is_complete(
+            "sealed trait Shape\ncase class Circle(radius: Double) extends Shape\n",
+            true
+        ));
+    }
+
+    #[test]
+    fn test_parse_key_override() {
+        assert_eq!(
+            parse(r#"case class Person(age: Int, userId: String // @key("user_id"))"#).unwrap(),
+            CaseClass {
+                name: "Person".to_string(),
+                type_params: vec![],
+                fields: vec![
+                    field("age"),
+                    Field {
+                        name: "userId".to_string(),
+                        key_override: Some("user_id".to_string()),
+                    },
+                ],
+            }
+        );
+        assert_eq!(
+            parse(r#"case class Person(age: Int, @JsonKey("user_id") userId: String)"#).unwrap(),
+            CaseClass {
+                name: "Person".to_string(),
+                type_params: vec![],
+                fields: vec![
+                    field("age"),
+                    Field {
+                        name: "userId".to_string(),
+                        key_override: Some("user_id".to_string()),
+                    },
+                ],
+            }
+        );
+        assert_eq!(
+            parse(indoc!(
+                r#"
+                case class Person(
+                  userId: String, // @key("user_id")
+                  age: Int
+                )"#
+            ))
+            .unwrap(),
+            CaseClass {
+                name: "Person".to_string(),
+                type_params: vec![],
+                fields: vec![
+                    Field {
+                        name: "userId".to_string(),
+                        key_override: Some("user_id".to_string()),
+                    },
+                    field("age"),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_companion_object_key_override() {
+        let class = CaseClass {
+            name: "Person".to_string(),
+            type_params: vec![],
+            fields: vec![
+                field("age"),
+                Field {
+                    name: "userId".to_string(),
+                    key_override: Some("user_id".to_string()),
+                },
+            ],
+        };
+        assert_eq!(
+            class.companion_object(Casing::Camel),
+            indoc!(
+                r#"
+                object Person {
+                  implicit lazy val encoder: Encoder[Person] = Encoder.forProduct2("age", "user_id")(a => (a.age, a.userId))
+
+                  implicit lazy val decoder: Decoder[Person] = Decoder.forProduct2("age", "user_id")(Person.apply)
+                }"#
+            )
+        );
+    }
 }